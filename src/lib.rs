@@ -5,10 +5,35 @@ use cargo_manifest::{Manifest, Product, Value};
 
 use std::ffi::OsString;
 use std::fs::{DirBuilder, File};
+use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process::Command;
 
+/// Default LZMA dictionary/window size (in MiB) used for `--package-archive` xz output.
+///
+/// This is larger than the xz2/liblzma stock default of 8 MiB, which helps shrink archives of
+/// large generated message/IDL trees.
+const DEFAULT_COMPRESSION_WINDOW_MB: u32 = 64;
+
+/// Compression format for `--package-archive`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Xz,
+    Gzip,
+}
+
+impl std::str::FromStr for Compression {
+    type Err = anyhow::Error;
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "xz" => Ok(Compression::Xz),
+            "gzip" => Ok(Compression::Gzip),
+            other => bail!("Unknown --compression value '{other}', expected 'xz' or 'gzip'"),
+        }
+    }
+}
+
 /// Arguments for both the wrapper and for `cargo build`.
 pub struct Args {
     /// The install base for this package (i.e. directory containing `lib`, `share` etc.)
@@ -21,6 +46,17 @@ pub struct Args {
     pub profile: String,
     /// The absolute path to the Cargo.toml file. Currently the --manifest-path option is not implemented.
     pub manifest_path: PathBuf,
+    /// If set, bundle the per-package install tree into a single compressed tarball at this path.
+    pub package_archive: Option<PathBuf>,
+    /// Compression format to use for `--package-archive`.
+    pub compression: Compression,
+    /// LZMA dictionary/window size (in MiB) to use when writing xz archives.
+    pub compression_window_mb: u32,
+    /// If set, remove exactly the files recorded in a previous install's manifest instead of
+    /// building and installing.
+    pub uninstall: bool,
+    /// If set, print the planned file operations and exit without touching the filesystem.
+    pub dry_run: bool,
 }
 
 /// Wrapper around [`Args`] that can also indicate the --help flag.
@@ -73,12 +109,27 @@ impl ArgsOrHelp {
                 .context("Package manifest does not exist")?
         };
 
+        let package_archive = args.opt_value_from_str("--package-archive")?;
+        let compression = args
+            .opt_value_from_str("--compression")?
+            .unwrap_or(Compression::Xz);
+        let compression_window_mb = args
+            .opt_value_from_str("--compression-window-mb")?
+            .unwrap_or(DEFAULT_COMPRESSION_WINDOW_MB);
+        let uninstall = args.contains("--uninstall");
+        let dry_run = args.contains("--dry-run");
+
         let res = Args {
             install_base,
             build_base,
             forwarded_args,
             profile,
             manifest_path,
+            package_archive,
+            compression,
+            compression_window_mb,
+            uninstall,
+            dry_run,
         };
 
         Ok(ArgsOrHelp::Args(res))
@@ -89,6 +140,12 @@ impl ArgsOrHelp {
         println!("Wrapper around cargo-build that installs compilation results and extra files to an ament/ROS 2 install space.\n");
         println!("USAGE:");
         println!("    cargo ament-build --install-base <INSTALL_DIR> -- <CARGO-BUILD-OPTIONS>");
+        println!();
+        println!("    --package-archive <PATH>          Also bundle the install space into a tarball at PATH.");
+        println!("    --compression <xz|gzip>           Compression format for --package-archive (default: xz).");
+        println!("    --compression-window-mb <MB>       LZMA dictionary/window size for xz output (default: {DEFAULT_COMPRESSION_WINDOW_MB}).");
+        println!("    --uninstall                       Remove exactly the files recorded in a previous install's manifest.");
+        println!("    --dry-run                         Print the planned file operations and exit without touching the filesystem.");
     }
 }
 
@@ -106,56 +163,319 @@ pub fn cargo(args: &[OsString], verb: &str) -> Result<Option<i32>> {
     Ok(exit_status.code())
 }
 
+/// A single filesystem action planned by an install step. See [`Plan`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlanAction {
+    CreateDir(PathBuf),
+    RemoveFile(PathBuf),
+    CopyFile { src: PathBuf, dest: PathBuf },
+    Symlink { target: PathBuf, dest: PathBuf },
+    WriteFile { dest: PathBuf, contents: String },
+    SetPermissions { path: PathBuf, mode: u32 },
+}
+
+impl std::fmt::Display for PlanAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlanAction::CreateDir(dir) => write!(f, "create-dir  {}", dir.display()),
+            PlanAction::RemoveFile(path) => write!(f, "remove-file {}", path.display()),
+            PlanAction::CopyFile { src, dest } => {
+                write!(f, "copy-file   {} -> {}", src.display(), dest.display())
+            }
+            PlanAction::Symlink { target, dest } => {
+                write!(f, "symlink     {} -> {}", dest.display(), target.display())
+            }
+            PlanAction::WriteFile { dest, .. } => write!(f, "write-file  {}", dest.display()),
+            PlanAction::SetPermissions { path, mode } => {
+                write!(f, "set-mode    {:o} {}", mode, path.display())
+            }
+        }
+    }
+}
+
+/// An ordered, structured list of filesystem actions produced by the install steps.
+///
+/// Building a [`Plan`] never modifies the filesystem (it only reads the sources and any previous
+/// install's manifest and files). `--dry-run` prints the plan as-is; a real run calls [`Plan::execute`] on that exact
+/// same value, so the two paths cannot diverge.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Plan(Vec<PlanAction>);
+
+impl Plan {
+    fn push(&mut self, action: PlanAction) {
+        self.0.push(action);
+    }
+
+    /// Append another plan's actions to the end of this one.
+    pub fn extend(&mut self, other: Plan) {
+        self.0.extend(other.0);
+    }
+
+    /// The planned actions, in the order they would be executed.
+    pub fn actions(&self) -> &[PlanAction] {
+        &self.0
+    }
+
+    /// Print every planned action, one per line, in execution order.
+    pub fn print(&self) {
+        for action in &self.0 {
+            println!("{action}");
+        }
+    }
+
+    /// Execute every planned action against the real filesystem, appending the destination of
+    /// every created file or symlink to `install_manifest`.
+    pub fn execute(&self, install_manifest: &mut Vec<PathBuf>) -> Result<()> {
+        for action in &self.0 {
+            match action {
+                PlanAction::CreateDir(dir) => {
+                    DirBuilder::new()
+                        .recursive(true)
+                        .create(dir)
+                        .with_context(|| format!("Failed to create directory '{}'", dir.display()))?;
+                }
+                PlanAction::RemoveFile(path) => {
+                    std::fs::remove_file(path)
+                        .with_context(|| format!("Failed to remove '{}'", path.display()))?;
+                }
+                PlanAction::CopyFile { src, dest } => {
+                    std::fs::copy(src, dest).with_context(|| {
+                        format!("Failed to copy '{}' to '{}'.", src.display(), dest.display())
+                    })?;
+                    install_manifest.push(dest.clone());
+                }
+                PlanAction::Symlink { target, dest } => {
+                    symlink(target, dest)
+                        .with_context(|| format!("Failed to create symlink '{}'", dest.display()))?;
+                    install_manifest.push(dest.clone());
+                }
+                PlanAction::WriteFile { dest, contents } => {
+                    std::fs::write(dest, contents)
+                        .with_context(|| format!("Failed to write '{}'", dest.display()))?;
+                    install_manifest.push(dest.clone());
+                }
+                PlanAction::SetPermissions { path, mode } => {
+                    #[cfg(unix)]
+                    {
+                        use std::os::unix::fs::PermissionsExt;
+                        std::fs::set_permissions(path, std::fs::Permissions::from_mode(*mode))
+                            .with_context(|| format!("Failed to set permissions on '{}'", path.display()))?;
+                    }
+                    #[cfg(not(unix))]
+                    {
+                        let _ = (path, mode);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
 /// This is comparable to ament_index_register_resource() in CMake
 pub fn create_package_marker(
     install_base: impl AsRef<Path>,
     marker_dir: &str,
     package_name: &str,
-) -> Result<()> {
+) -> Result<Plan> {
     let mut path = install_base
         .as_ref()
         .join("share/ament_index/resource_index");
     path.push(marker_dir);
-    DirBuilder::new()
-        .recursive(true)
-        .create(&path)
-        .with_context(|| {
-            format!(
-                "Failed to create package marker directory '{}'",
-                path.display()
-            )
-        })?;
+    let mut plan = Plan::default();
+    plan.push(PlanAction::CreateDir(path.clone()));
     path.push(package_name);
-    File::create(&path)
-        .with_context(|| format!("Failed to create package marker '{}'", path.display()))?;
-    Ok(())
+    plan.push(PlanAction::WriteFile {
+        dest: path,
+        contents: String::new(),
+    });
+    Ok(plan)
+}
+
+/// Resolve the crate's `[dependencies]` down to the subset that are themselves ament/ROS 2 Rust
+/// packages, i.e. ones whose install tree registers a `share/ament_index/resource_index/packages/<name>`
+/// marker somewhere on `AMENT_PREFIX_PATH`. Dependencies that don't resolve to an ament package
+/// (plain crates.io crates, etc.) are skipped.
+pub fn resolve_ament_run_dependencies(manifest: &Manifest) -> Vec<String> {
+    let Some(dependencies) = &manifest.dependencies else {
+        return Vec::new();
+    };
+    let prefixes: Vec<PathBuf> = std::env::var("AMENT_PREFIX_PATH")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|entry| !entry.is_empty())
+        .map(PathBuf::from)
+        .collect();
+
+    dependencies
+        .iter()
+        // A dependency can rename its crate via `package = "..."`; resolve under the real name,
+        // not the local alias.
+        .map(|(key, dep)| dep.package().map(str::to_string).unwrap_or_else(|| key.clone()))
+        .filter(|name| {
+            prefixes.iter().any(|prefix| {
+                prefix
+                    .join("share/ament_index/resource_index/packages")
+                    .join(name)
+                    .is_file()
+            })
+        })
+        .collect()
 }
 
-/// Copies files or directories.
-fn copy(src: impl AsRef<Path>, dest_dir: impl AsRef<Path>) -> Result<()> {
+/// Register `run_dependencies` (as resolved by [`resolve_ament_run_dependencies`]) under
+/// `package_run_dependencies` in the ament resource index. A no-op plan if there are no ament
+/// run dependencies.
+pub fn create_package_run_dependencies_marker(
+    install_base: impl AsRef<Path>,
+    package_name: &str,
+    run_dependencies: &[String],
+) -> Result<Plan> {
+    let mut plan = Plan::default();
+    if run_dependencies.is_empty() {
+        return Ok(plan);
+    }
+    let mut path = install_base
+        .as_ref()
+        .join("share/ament_index/resource_index/package_run_dependencies");
+    plan.push(PlanAction::CreateDir(path.clone()));
+    path.push(package_name);
+    plan.push(PlanAction::WriteFile {
+        dest: path,
+        contents: run_dependencies.join("\n"),
+    });
+    Ok(plan)
+}
+
+/// Plans copying a file or directory tree into `dest_dir`, preserving permissions and symlinks.
+///
+/// This is a pure-Rust equivalent of `cp -r` (no shelling out, so it behaves identically on
+/// Windows), which keeps the executable bit on installed scripts and recreates symlinks as
+/// symlinks rather than following and flattening them.
+fn copy(src: impl AsRef<Path>, dest_dir: impl AsRef<Path>) -> Result<Plan> {
     let src = src.as_ref();
-    let dest = dest_dir.as_ref().join(src.file_name().unwrap());
-    if src.is_dir() {
-        std::fs::create_dir_all(&dest)?;
-        for entry in std::fs::read_dir(src)? {
-            let entry = entry?;
-            if entry.file_type()?.is_dir() {
-                copy(entry.path(), &dest)?;
-            } else {
-                std::fs::copy(entry.path(), dest.join(entry.file_name()))?;
+    let dest_dir = dest_dir.as_ref();
+    let file_name = src
+        .file_name()
+        .ok_or_else(|| anyhow!("Source path '{}' has no file name", src.display()))?;
+    if !src.exists() && !src.is_symlink() {
+        bail!("File or dir '{}' does not exist", src.display());
+    }
+    // `root` guards against symlinks escaping the tree being copied. If `src` is itself a
+    // symlink (rather than a directory being walked), using `src` as root would make the guard
+    // compare against its own leaf path, rejecting virtually every real target; use its parent
+    // directory instead.
+    let root = if src.is_symlink() { src.parent().unwrap_or(src) } else { src };
+    let mut plan = Plan::default();
+    copy_recursive(root, src, &dest_dir.join(file_name), &mut plan)?;
+    Ok(plan)
+}
+
+/// Recursive worker for [`copy`]. `root` is the original source root, used to guard against
+/// symlinks that point outside of the tree being copied.
+fn copy_recursive(root: &Path, src: &Path, dest: &Path, plan: &mut Plan) -> Result<()> {
+    let file_type = std::fs::symlink_metadata(src)
+        .with_context(|| format!("Failed to stat '{}'", src.display()))?
+        .file_type();
+
+    if file_type.is_symlink() {
+        let target = std::fs::read_link(src)
+            .with_context(|| format!("Failed to read symlink '{}'", src.display()))?;
+        // `src` and `root` may be relative or go through symlinks, while `resolved` below is
+        // canonical; compare canonical forms on both sides so in-tree links are not refused.
+        let canonical = |p: &Path| {
+            let p = if p.as_os_str().is_empty() { Path::new(".") } else { p };
+            p.canonicalize()
+                .with_context(|| format!("Failed to resolve '{}'", p.display()))
+        };
+        let root = canonical(root)?;
+        let joined = canonical(src.parent().unwrap_or(src))?.join(&target);
+        // The target may not exist yet (a dangling symlink, or one through not-yet-created
+        // intermediate dirs), so fall back to a lexical normalization rather than skipping the
+        // check entirely.
+        let resolved = joined
+            .canonicalize()
+            .unwrap_or_else(|_| normalize_lexically(&joined));
+        if !resolved.starts_with(&root) {
+            bail!(
+                "Refusing to copy symlink '{}': target '{}' escapes '{}'",
+                src.display(),
+                resolved.display(),
+                root.display()
+            );
+        }
+        plan.push(PlanAction::Symlink {
+            target,
+            dest: dest.to_path_buf(),
+        });
+    } else if file_type.is_dir() {
+        plan.push(PlanAction::CreateDir(dest.to_path_buf()));
+        for entry in std::fs::read_dir(src)
+            .with_context(|| format!("Failed to read directory '{}'", src.display()))?
+        {
+            let entry = entry.with_context(|| format!("Failed to read entry in '{}'", src.display()))?;
+            copy_recursive(root, &entry.path(), &dest.join(entry.file_name()), plan)?;
+        }
+        push_permission_action(src, dest, plan)?;
+    } else {
+        plan.push(PlanAction::CopyFile {
+            src: src.to_path_buf(),
+            dest: dest.to_path_buf(),
+        });
+        push_permission_action(src, dest, plan)?;
+    }
+    Ok(())
+}
+
+/// Resolve `.` and `..` components of `path` without touching the filesystem (unlike
+/// [`Path::canonicalize`]), for use when a path may not exist yet.
+fn normalize_lexically(path: &Path) -> PathBuf {
+    let mut result = PathBuf::new();
+    for component in path.components() {
+        match component {
+            std::path::Component::ParentDir => {
+                result.pop();
             }
+            std::path::Component::CurDir => {}
+            other => result.push(other.as_os_str()),
         }
-    } else if src.is_file() {
-        std::fs::copy(&src, &dest).with_context(|| {
-            format!(
-                "Failed to copy '{}' to '{}'.",
-                src.display(),
-                dest.display()
-            )
-        })?;
+    }
+    result
+}
+
+/// Create a symlink at `dest` pointing to `target`, on whichever platform we're running on.
+#[cfg(unix)]
+fn symlink(target: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, dest)
+}
+
+#[cfg(windows)]
+fn symlink(target: &Path, dest: &Path) -> std::io::Result<()> {
+    if target.is_dir() {
+        std::os::windows::fs::symlink_dir(target, dest)
     } else {
-        bail!("File or dir '{}' does not exist", src.display())
+        std::os::windows::fs::symlink_file(target, dest)
     }
+}
+
+/// Plan re-applying `src`'s Unix permission bits onto `dest`. A no-op on non-Unix platforms,
+/// since those don't have the same permission-bit model.
+#[cfg(unix)]
+fn push_permission_action(src: &Path, dest: &Path, plan: &mut Plan) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = std::fs::symlink_metadata(src)
+        .with_context(|| format!("Failed to stat '{}'", src.display()))?
+        .permissions()
+        .mode();
+    plan.push(PlanAction::SetPermissions {
+        path: dest.to_path_buf(),
+        mode,
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn push_permission_action(_src: &Path, _dest: &Path, _plan: &mut Plan) -> Result<()> {
     Ok(())
 }
 
@@ -168,14 +488,13 @@ pub fn install_package(
     manifest_path: impl AsRef<Path>,
     package_name: &str,
     manifest: &Manifest,
-) -> Result<()> {
+) -> Result<Plan> {
+    let mut plan = Plan::default();
     // Install source code
     // This is special-cased (and not simply added to the list of things to install below)
     let dest_dir = install_base.as_ref().to_owned().join("share").join(package_name).join("rust");
-    if dest_dir.is_dir() {
-        std::fs::remove_dir_all(&dest_dir)?;
-    }
-    DirBuilder::new().recursive(true).create(&dest_dir)?;
+    plan.extend(plan_remove_previous_install(&install_base, package_name, &dest_dir)?);
+    plan.push(PlanAction::CreateDir(dest_dir.clone()));
     // unwrap is ok since it has been validated in main
     let package = manifest.package.as_ref().unwrap();
     // The entry for the build script can be empty (in which case build.rs is implicitly used if it
@@ -188,18 +507,18 @@ pub fn install_package(
     };
     if let Some(filename) = build {
         let src = package_path.as_ref().join(filename);
-        copy(src, &dest_dir)?;
+        plan.extend(copy(src, &dest_dir)?);
     }
 
-    copy(package_path.as_ref().join("src"), &dest_dir)?;
-    copy(manifest_path.as_ref(), &dest_dir)?;
-    copy(manifest_path.as_ref().with_extension("lock"), &dest_dir)?;
+    plan.extend(copy(package_path.as_ref().join("src"), &dest_dir)?);
+    plan.extend(copy(manifest_path.as_ref(), &dest_dir)?);
+    plan.extend(copy(manifest_path.as_ref().with_extension("lock"), &dest_dir)?);
     // unwrap is ok since we pushed to the path before
-    copy(
+    plan.extend(copy(
         package_path.as_ref().join("package.xml"),
         dest_dir.parent().unwrap(),
-    )?;
-    Ok(())
+    )?);
+    Ok(plan)
 }
 
 /// Copy the binaries to a location where they will be found by ROS 2 tools (the lib dir)
@@ -209,26 +528,13 @@ pub fn install_binaries(
     package_name: &str,
     profile: &str,
     binaries: &[Product],
-) -> Result<()> {
+    run_dependencies: &[String],
+) -> Result<Plan> {
+    let mut plan = Plan::default();
     let src_dir = build_base.as_ref().join(profile);
     let dest_dir = install_base.as_ref().join("lib").join(package_name);
-    if dest_dir.is_dir() {
-        std::fs::remove_dir_all(&dest_dir)?;
-    }
+    plan.extend(plan_remove_previous_install(&install_base, package_name, &dest_dir)?);
 
-    // Copy binaries
-    for binary in binaries {
-        let name = binary
-            .name
-            .as_ref()
-            .ok_or(anyhow!("Binary without name found"))?;
-        let src = src_dir.join(name);
-        let dest = dest_dir.join(name);
-        // Create destination directory
-        DirBuilder::new().recursive(true).create(&dest_dir)?;
-        std::fs::copy(&src, &dest)
-            .context(format!("Failed to copy binary from '{}'", src.display()))?;
-    }
     // If there is a shared or static library, copy it too
     // See https://doc.rust-lang.org/reference/linkage.html for an explanation of suffixes
     let prefix_suffix_combinations = [
@@ -238,21 +544,36 @@ pub fn install_binaries(
         ("", "dll"),
         ("", "lib"),
     ];
-    let mut libraries : Vec<String> = vec![];
+    let mut libraries: Vec<String> = vec![];
+    let mut library_sources: Vec<PathBuf> = vec![];
     for (prefix, suffix) in prefix_suffix_combinations {
         let filename = String::from(prefix) + package_name + "." + suffix;
         let src = src_dir.join(&filename);
-        let dest = dest_dir.join(&filename);
         if src.is_file() {
             // We found a library, add this to the list of libraries.
-            libraries.push(filename.to_owned());
-            // Create destination directory
-            DirBuilder::new().recursive(true).create(&dest_dir)?;
-            std::fs::copy(&src, &dest)
-                .context(format!("Failed to copy library from '{}'", src.display()))?;
+            libraries.push(filename);
+            library_sources.push(src);
         }
     }
 
+    if !binaries.is_empty() || !library_sources.is_empty() {
+        plan.push(PlanAction::CreateDir(dest_dir.clone()));
+    }
+    // Copy binaries
+    for binary in binaries {
+        let name = binary
+            .name
+            .as_ref()
+            .ok_or(anyhow!("Binary without name found"))?;
+        let src = src_dir.join(name);
+        let dest = dest_dir.join(name);
+        plan.push(PlanAction::CopyFile { src, dest });
+    }
+    for src in library_sources {
+        let dest = dest_dir.join(src.file_name().unwrap());
+        plan.push(PlanAction::CopyFile { src, dest });
+    }
+
     // Build scripts are not allowed to write outside of OUT_DIR as per
     // https://doc.rust-lang.org/cargo/reference/build-script-examples.html
 
@@ -289,10 +610,8 @@ pub fn install_binaries(
         // Force all includes into the package_name subdirectory... this breaks with cmake, but it
         // is better as it avoids conflicts.
         let include_dir = install_base.as_ref().to_owned().join("include").join(package_name);
-        if include_dir.is_dir() {
-            std::fs::remove_dir_all(&include_dir)?;
-        }
-        DirBuilder::new().recursive(true).create(&include_dir)?;
+        plan.extend(plan_remove_previous_install(&install_base, package_name, &include_dir)?);
+        plan.push(PlanAction::CreateDir(include_dir.clone()));
 
         // Now iterate over all found roots and copy relevant things.
         for d in include_roots {
@@ -302,24 +621,16 @@ pub fn install_binaries(
                 {
                     continue;  // Skip the marker item.
                 }
-                // Recursive copy is not available in std::fs, lets just use cp.
-                Command::new("cp")
-                .arg("-r")
-                .arg(entry.path())
-                .arg(&include_dir)
-                .output()
-                .context(format!("Failed to copy into include dir from '{:?}'", entry.path()))?;
+                plan.extend(copy(entry.path(), &include_dir)?);
             }
-            
+
         }
     }
 
     // Now that we know what libraries exist, we can create the cmake config file.
     let package_cmake_dir = install_base.as_ref().to_owned().join("share").join(package_name).join("cmake");
-    if package_cmake_dir.is_dir() {
-        std::fs::remove_dir_all(&package_cmake_dir)?;
-    }
-    DirBuilder::new().recursive(true).create(&package_cmake_dir)?;
+    plan.extend(plan_remove_previous_install(&install_base, package_name, &package_cmake_dir)?);
+    plan.push(PlanAction::CreateDir(package_cmake_dir.clone()));
     let cmake_template = include_str!("cmakeConfig.cmake.in");
     let supported_replaces = [("@PACKAGE_NAME@", package_name),
                               ("@PACKAGE_LIBRARY_LIST@", &libraries.join(&";")),
@@ -328,8 +639,24 @@ pub fn install_binaries(
     for (pattern, replace) in supported_replaces {
         config_file = config_file.replace(pattern, replace);
     }
-    std::fs::write(package_cmake_dir.join(&format!("{package_name}Config.cmake")), config_file)?;
-    Ok(())
+    if !run_dependencies.is_empty() {
+        config_file.push_str("\n# Run dependencies inferred from Cargo.toml's [dependencies].\n");
+        for dep in run_dependencies {
+            config_file.push_str(&format!("find_package({dep} REQUIRED)\n"));
+            // Not every ament_cmake package exports a namespaced imported target; plain
+            // ament_cmake C++ packages conventionally only set `<dep>_LIBRARIES`/
+            // `<dep>_INCLUDE_DIRS`. Prefer the target when it exists and fall back to those.
+            config_file.push_str(&format!(
+                "if(TARGET {dep}::{dep})\n  list(APPEND {package_name}_LIBRARIES {dep}::{dep})\nelse()\n  list(APPEND {package_name}_LIBRARIES ${{{dep}_LIBRARIES}})\n  list(APPEND {package_name}_INCLUDE_DIRS ${{{dep}_INCLUDE_DIRS}})\nendif()\n"
+            ));
+        }
+    }
+    let cmake_config_path = package_cmake_dir.join(format!("{package_name}Config.cmake"));
+    plan.push(PlanAction::WriteFile {
+        dest: cmake_config_path,
+        contents: config_file,
+    });
+    Ok(plan)
 }
 
 /// Copy selected files/directories to the share dir.
@@ -338,24 +665,25 @@ pub fn install_files_from_metadata(
     package_path: impl AsRef<Path>,
     package_name: &str,
     metadata: Option<&Value>,
-) -> Result<()> {
+) -> Result<Plan> {
+    let mut plan = Plan::default();
     // Unpack the metadata entry
     let metadata_table = match metadata {
         Some(Value::Table(tab)) => tab,
-        _ => return Ok(()),
+        _ => return Ok(plan),
     };
     let metadata_ros_table = match metadata_table.get("ros") {
         Some(Value::Table(tab)) => tab,
-        _ => return Ok(()),
+        _ => return Ok(plan),
     };
     for subdir in ["share", "include", "lib"] {
         let dest = install_base.as_ref().join(subdir).join(package_name);
-        DirBuilder::new().recursive(true).create(&dest)?;
+        plan.push(PlanAction::CreateDir(dest.clone()));
         let key = format!("install_to_{subdir}");
         let install_array = match metadata_ros_table.get(&key) {
             Some(Value::Array(arr)) => arr,
             Some(_) => bail!("The [package.metadata.ros.{key}] entry is not an array"),
-            _ => return Ok(()),
+            _ => return Ok(plan),
         };
         let install_entries = install_array
             .iter()
@@ -368,12 +696,381 @@ pub fn install_files_from_metadata(
             .collect::<Result<Vec<_>, _>>()?;
         for rel_path in install_entries {
             let src = package_path.as_ref().join(&rel_path);
-            copy(&src, &dest).with_context(|| {
+            let copy_plan = copy(&src, &dest).with_context(|| {
                 format!(
                     "Could not process [package.metadata.ros.{key}] entry '{rel_path}'",
                 )
             })?;
+            plan.extend(copy_plan);
+        }
+    }
+    Ok(plan)
+}
+
+/// Path of the install manifest for `package_name`, relative to `install_base`.
+fn install_manifest_path(install_base: impl AsRef<Path>, package_name: &str) -> PathBuf {
+    install_base
+        .as_ref()
+        .join("share")
+        .join(package_name)
+        .join("rust")
+        .join("install_manifest.txt")
+}
+
+/// Paths recorded by a previous install of `package_name`, or an empty list if no manifest
+/// exists yet (e.g. this is the first install).
+fn previous_install_manifest(install_base: impl AsRef<Path>, package_name: &str) -> Result<Vec<PathBuf>> {
+    let manifest_path = install_manifest_path(&install_base, package_name);
+    match std::fs::read_to_string(&manifest_path) {
+        Ok(contents) => Ok(contents
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(PathBuf::from)
+            .collect()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err)
+            .with_context(|| format!("Failed to read install manifest '{}'", manifest_path.display())),
+    }
+}
+
+/// Plan the removal of files under `subtree` that were recorded by a previous install of
+/// `package_name`, without touching anything else that happens to live alongside them (e.g. a
+/// directory shared with another package). Used to clear out a previous install of `subtree`
+/// before repopulating it, instead of `remove_dir_all`-ing it wholesale.
+fn plan_remove_previous_install(
+    install_base: impl AsRef<Path>,
+    package_name: &str,
+    subtree: &Path,
+) -> Result<Plan> {
+    let mut plan = Plan::default();
+    for path in previous_install_manifest(install_base, package_name)? {
+        if path.starts_with(subtree) && (path.is_symlink() || path.is_file()) {
+            plan.push(PlanAction::RemoveFile(path));
+        }
+    }
+    Ok(plan)
+}
+
+/// Write every path recorded by the install steps to `${install_base}/share/${package}/rust/install_manifest.txt`,
+/// one path per line, so a later `--uninstall` can remove exactly what was installed. Paths are
+/// written as recorded, i.e. joined onto `install_base`: they are absolute only if `install_base`
+/// is.
+pub fn write_install_manifest(
+    install_base: impl AsRef<Path>,
+    package_name: &str,
+    install_manifest: &[PathBuf],
+) -> Result<()> {
+    let manifest_path = install_manifest_path(&install_base, package_name);
+    let mut contents = String::new();
+    for path in install_manifest {
+        contents.push_str(&path.display().to_string());
+        contents.push('\n');
+    }
+    std::fs::write(&manifest_path, contents)
+        .with_context(|| format!("Failed to write install manifest '{}'", manifest_path.display()))?;
+    Ok(())
+}
+
+/// Remove exactly the files recorded by a previous [`write_install_manifest`] call, pruning any
+/// parent directories that become empty as a result.
+pub fn uninstall_package(install_base: impl AsRef<Path>, package_name: &str) -> Result<()> {
+    let manifest_path = install_manifest_path(&install_base, package_name);
+    let contents = std::fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Failed to read install manifest '{}'", manifest_path.display()))?;
+
+    let mut parents_to_prune = std::collections::BTreeSet::new();
+    for line in contents.lines().filter(|line| !line.is_empty()) {
+        let path = PathBuf::from(line);
+        if path.is_symlink() || path.is_file() {
+            std::fs::remove_file(&path)
+                .with_context(|| format!("Failed to remove '{}'", path.display()))?;
+        }
+        if let Some(parent) = path.parent() {
+            parents_to_prune.insert(parent.to_path_buf());
+        }
+    }
+    // The manifest itself is also removed, but only after every recorded path has been deleted.
+    std::fs::remove_file(&manifest_path)
+        .with_context(|| format!("Failed to remove install manifest '{}'", manifest_path.display()))?;
+
+    // Prune now-empty parent directories, deepest first, stopping at install_base.
+    let install_base = install_base.as_ref();
+    let mut parents: Vec<PathBuf> = parents_to_prune.into_iter().collect();
+    parents.sort_by_key(|p| std::cmp::Reverse(p.components().count()));
+    for dir in parents {
+        let mut dir = dir.as_path();
+        while dir.starts_with(install_base) && dir != install_base {
+            match std::fs::read_dir(dir) {
+                Ok(mut entries) => {
+                    if entries.next().is_some() {
+                        break;
+                    }
+                    std::fs::remove_dir(dir)
+                        .with_context(|| format!("Failed to remove empty directory '{}'", dir.display()))?;
+                }
+                Err(_) => break,
+            }
+            match dir.parent() {
+                Some(parent) => dir = parent,
+                None => break,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Recursively collect every file and symlink below `dir`, as paths relative to `root`.
+///
+/// The result is not sorted; callers that need a stable archive order must sort it themselves.
+fn collect_archive_entries(root: &Path, dir: &Path, found: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory '{}'", dir.display()))?
+    {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() && !path.is_symlink() {
+            collect_archive_entries(root, &path, found)?;
+        } else {
+            found.push(
+                path.strip_prefix(root)
+                    .expect("entry is always below root")
+                    .to_path_buf(),
+            );
         }
     }
     Ok(())
 }
+
+/// Bundle the entire per-package install tree (`share/`, `lib/`, `include/`, ...) produced by
+/// [`install_package`], [`install_binaries`] and [`install_files_from_metadata`] into a single
+/// compressed tarball at `archive_path`.
+///
+/// Entries are added in sorted order with normalized metadata (uid/gid/mtime/mode), so repeated
+/// builds of unchanged inputs produce byte-identical archives regardless of who built them.
+pub fn package_archive(
+    install_base: impl AsRef<Path>,
+    archive_path: impl AsRef<Path>,
+    compression: Compression,
+    compression_window_mb: u32,
+) -> Result<()> {
+    let install_base = install_base.as_ref();
+    let archive_path = archive_path.as_ref();
+
+    let mut entries = Vec::new();
+    collect_archive_entries(install_base, install_base, &mut entries)?;
+    entries.sort();
+
+    let file = File::create(archive_path)
+        .with_context(|| format!("Failed to create archive '{}'", archive_path.display()))?;
+
+    let writer: Box<dyn Write> = match compression {
+        Compression::Xz => {
+            // Use a larger LZMA dictionary/window than xz2's stock 8 MiB default, so archives of
+            // big generated message/IDL trees shrink further.
+            let mut lzma_options = xz2::stream::LzmaOptions::new_preset(6)?;
+            let dict_size = match compression_window_mb.checked_mul(1024 * 1024) {
+                Some(bytes) => bytes,
+                None => bail!(
+                    "--compression-window-mb value '{compression_window_mb}' overflows when converted to bytes"
+                ),
+            };
+            lzma_options.dict_size(dict_size);
+            let mut filters = xz2::stream::Filters::new();
+            filters.lzma2(&lzma_options);
+            let stream =
+                xz2::stream::Stream::new_stream_encoder(&filters, xz2::stream::Check::Crc64)
+                    .context("Failed to initialize xz encoder")?;
+            Box::new(xz2::write::XzEncoder::new_stream(file, stream))
+        }
+        Compression::Gzip => {
+            Box::new(flate2::write::GzEncoder::new(file, flate2::Compression::best()))
+        }
+    };
+
+    let mut builder = tar::Builder::new(writer);
+    for entry in &entries {
+        let full_path = install_base.join(entry);
+        let metadata = std::fs::symlink_metadata(&full_path)
+            .with_context(|| format!("Failed to stat '{}'", full_path.display()))?;
+        let mut header = tar::Header::new_gnu();
+        // `Deterministic` sets uid/gid to 0, mtime to a fixed timestamp and mode to 0o644/0o755
+        // (keeping only the owner execute bit), so unchanged inputs
+        // produce byte-identical archives regardless of which user or machine built them.
+        header.set_metadata_in_mode(&metadata, tar::HeaderMode::Deterministic);
+
+        if metadata.file_type().is_symlink() {
+            let target = std::fs::read_link(&full_path)
+                .with_context(|| format!("Failed to read symlink '{}'", full_path.display()))?;
+            builder.append_link(&mut header, entry, target)?;
+        } else {
+            header.set_size(metadata.len());
+            header.set_cksum();
+            let mut f = File::open(&full_path)
+                .with_context(|| format!("Failed to open '{}'", full_path.display()))?;
+            builder.append_data(&mut header, entry, &mut f)?;
+        }
+    }
+    builder
+        .into_inner()
+        .context("Failed to finalize archive")?
+        .flush()
+        .context("Failed to flush archive")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh, uniquely named scratch directory under the system temp dir, removed on drop.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "cargo-ament-build-test-{name}-{}",
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            TempDir(dir)
+        }
+
+        fn path(&self) -> &Path {
+            &self.0
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Dry-run planning must not diverge from what a real run does, including on a rebuild that
+    /// has to clean up after a previous install: an install, followed by a source file being
+    /// removed (as happens between incremental rebuilds), followed by a second install, must
+    /// produce the same plan whether that plan stands in for `--dry-run` or is the one actually
+    /// executed, and that plan must actually prune the stale file's previously installed copy
+    /// via [`plan_remove_previous_install`] rather than leaving it behind.
+    #[test]
+    fn dry_run_plan_matches_real_run() {
+        let scratch = TempDir::new("install-package");
+        let package_path = scratch.path().join("package");
+        std::fs::create_dir_all(package_path.join("src")).unwrap();
+        std::fs::write(
+            package_path.join("Cargo.toml"),
+            "[package]\nname = \"fixture\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        std::fs::write(package_path.join("src").join("lib.rs"), "").unwrap();
+        std::fs::write(package_path.join("src").join("stale.rs"), "").unwrap();
+        std::fs::write(package_path.join("Cargo.lock"), "").unwrap();
+        std::fs::write(package_path.join("package.xml"), "<package/>").unwrap();
+
+        let manifest_path = package_path.join("Cargo.toml");
+        let manifest = Manifest::from_path(&manifest_path).unwrap();
+        let install_base = scratch.path().join("install");
+        let rust_dir = install_base.join("share").join("fixture").join("rust");
+
+        // First install: lays down lib.rs and stale.rs, and records what it installed.
+        let first_plan = install_package(
+            &install_base,
+            &package_path,
+            &manifest_path,
+            "fixture",
+            &manifest,
+        )
+        .unwrap();
+        let mut install_manifest = Vec::new();
+        first_plan.execute(&mut install_manifest).unwrap();
+        write_install_manifest(&install_base, "fixture", &install_manifest).unwrap();
+        assert!(rust_dir.join("src").join("stale.rs").is_file());
+
+        // stale.rs is removed from the source between builds, as happens in an incremental rebuild.
+        std::fs::remove_file(package_path.join("src").join("stale.rs")).unwrap();
+
+        let dry_run_plan = install_package(
+            &install_base,
+            &package_path,
+            &manifest_path,
+            "fixture",
+            &manifest,
+        )
+        .unwrap();
+        let real_run_plan = install_package(
+            &install_base,
+            &package_path,
+            &manifest_path,
+            "fixture",
+            &manifest,
+        )
+        .unwrap();
+        assert_eq!(dry_run_plan, real_run_plan);
+
+        let mut install_manifest = Vec::new();
+        real_run_plan.execute(&mut install_manifest).unwrap();
+
+        assert!(rust_dir.join("Cargo.toml").is_file());
+        assert!(rust_dir.join("src").join("lib.rs").is_file());
+        assert!(!rust_dir.join("src").join("stale.rs").exists());
+    }
+
+    /// Uninstalling must remove exactly what was recorded, prune the directories that become
+    /// empty as a result, and leave a sibling package's files (sharing a parent directory)
+    /// completely untouched.
+    #[test]
+    fn uninstall_removes_only_recorded_paths() {
+        let scratch = TempDir::new("uninstall-package");
+        let package_path = scratch.path().join("package");
+        std::fs::create_dir_all(package_path.join("src")).unwrap();
+        std::fs::write(
+            package_path.join("Cargo.toml"),
+            "[package]\nname = \"fixture\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+        std::fs::write(package_path.join("src").join("lib.rs"), "").unwrap();
+        std::fs::write(package_path.join("Cargo.lock"), "").unwrap();
+        std::fs::write(package_path.join("package.xml"), "<package/>").unwrap();
+
+        let manifest_path = package_path.join("Cargo.toml");
+        let manifest = Manifest::from_path(&manifest_path).unwrap();
+        let install_base = scratch.path().join("install");
+
+        let plan = install_package(
+            &install_base,
+            &package_path,
+            &manifest_path,
+            "fixture",
+            &manifest,
+        )
+        .unwrap();
+        let mut install_manifest = Vec::new();
+        plan.execute(&mut install_manifest).unwrap();
+        write_install_manifest(&install_base, "fixture", &install_manifest).unwrap();
+
+        // A sibling package sharing the `share` directory must survive the uninstall untouched.
+        let sibling_file = install_base.join("share").join("other-pkg").join("marker");
+        std::fs::create_dir_all(sibling_file.parent().unwrap()).unwrap();
+        std::fs::write(&sibling_file, "").unwrap();
+
+        let fixture_dir = install_base.join("share").join("fixture");
+        assert!(fixture_dir.join("rust").join("Cargo.toml").is_file());
+
+        uninstall_package(&install_base, "fixture").unwrap();
+
+        assert!(
+            !fixture_dir.exists(),
+            "fixture's own install dir should be pruned once empty"
+        );
+        assert!(
+            install_base.join("share").is_dir(),
+            "a directory shared with another package must not be pruned"
+        );
+        assert!(
+            sibling_file.is_file(),
+            "a sibling package's files must survive an unrelated uninstall"
+        );
+        assert!(!install_manifest_path(&install_base, "fixture").exists());
+    }
+}